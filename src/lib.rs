@@ -2,36 +2,154 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::os::raw::c_char;
 use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::{ffi::CStr, str};
 
+use arc_swap::ArcSwap;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use aho_corasick::AhoCorasick;
+use aho_corasick::{
+    AhoCorasick, AhoCorasickBuilder, AhoCorasickKind, Anchored, Input, MatchKind, StartKind,
+};
 use serde::Deserialize;
 
 // ---------- Data structures ----------
 
 // One Aho–Corasick matcher per route_id
 // route_id is a u32 (you decide how to map URIs -> route_id in Lua)
-static ROUTE_ENGINES: Lazy<RwLock<HashMap<u32, Arc<AhoCorasick>>>> =
-    Lazy::new(|| RwLock::new(HashMap::new()));
+//
+// Held in an `ArcSwap` so the matching hot path is wait-free: a query does a
+// `load()`, reads the route's `RouteEngine` (matcher, flags and atomic stats)
+// through the guard, while loads and clears rebuild the whole map and
+// `store()`/`rcu()` it (read-copy-update). `engine_check_response_for_route`
+// and `engine_scan_response_for_route` need nothing outside the payload and
+// take no lock; `engine_evaluate_route` also reads `ROUTE_META` under its
+// `RwLock`.
+static ROUTE_ENGINES: Lazy<ArcSwap<HashMap<u32, Arc<RouteEngine>>>> =
+    Lazy::new(|| ArcSwap::from_pointee(HashMap::new()));
 
 // Optional: keep track of how many patterns per route (for debugging / metrics)
 static ROUTE_PATTERN_COUNTS: Lazy<RwLock<HashMap<u32, usize>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
-/// Rules expected from control plane (after base64 decode) as JSON:
-/// ["rm -rf", "DROP TABLE", "curl http"]
+// Per-route rule metadata, parallel (by index) to the patterns loaded into the
+// route's `AhoCorasick`. `pattern_index` from a match indexes straight into this.
+static ROUTE_META: Lazy<RwLock<HashMap<u32, Vec<RuleMeta>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Rules expected from control plane (after base64 decode) as JSON.
 ///
-/// If you later want richer metadata (ids, severity, etc.), you can wrap this
-/// in a struct and change the deserialization accordingly.
+/// Two forms are accepted. The legacy form is a plain array of pattern strings:
+///   ["rm -rf", "DROP TABLE", "curl http"]
+/// The rich form carries per-rule metadata:
+///   [{"id":"SQLI-1","pattern":"DROP TABLE","severity":5,"action":"block"}]
+/// Both may be mixed in one array. In the rich form `pattern` is required; a
+/// missing `id` falls back to the pattern text, `severity` to 0 and `action`
+/// to empty.
 #[derive(Debug, Deserialize)]
-struct RuleList(Vec<String>);
+#[serde(untagged)]
+enum RuleEntry {
+    Plain(String),
+    Rich(RuleObject),
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleObject {
+    #[serde(default)]
+    id: Option<String>,
+    pattern: String,
+    #[serde(default)]
+    severity: u32,
+    #[serde(default)]
+    action: Option<String>,
+}
+
+/// Metadata kept for a single loaded rule, stored in index order so a match's
+/// `pattern_index` maps straight back to rule identity.
+#[derive(Debug, Clone)]
+struct RuleMeta {
+    #[allow(dead_code)]
+    id: String,
+    severity: u32,
+    #[allow(dead_code)]
+    action: String,
+}
+
+// Live streaming scanner handles. A proxy feeds response body buffers one at a
+// time; each handle carries the route's matcher plus a tail carry-over so a
+// pattern straddling two chunks is still detected.
+static STREAMS: Lazy<RwLock<HashMap<u64, StreamState>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+// Monotonic source of opaque stream handle ids (0 is reserved for "failure").
+static STREAM_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// Match `Drop Table` the same as `DROP TABLE` (`ascii_case_insensitive`).
+pub const ENGINE_FLAG_CASE_INSENSITIVE: u32 = 1 << 0;
+/// Build with [`MatchKind::LeftmostLongest`] instead of standard semantics.
+pub const ENGINE_FLAG_LEFTMOST_LONGEST: u32 = 1 << 1;
+/// Only report matches anchored at the start of the content.
+pub const ENGINE_FLAG_ANCHORED: u32 = 1 << 2;
+/// Enable rare-byte prefilters so the search can skip most of the input.
+pub const ENGINE_FLAG_PREFILTER: u32 = 1 << 3;
+
+// Automaton kind selection lives in bits 4-5 of the flags word.
+/// Let the library pick the automaton kind (the default).
+pub const ENGINE_KIND_AUTO: u32 = 0 << 4;
+/// Force a DFA: fastest matching, but can use a lot of memory on large sets.
+pub const ENGINE_KIND_DFA: u32 = 1 << 4;
+/// Contiguous NFA: a balance of matching speed and memory use.
+pub const ENGINE_KIND_CONTIGUOUS_NFA: u32 = 2 << 4;
+/// Noncontiguous NFA: smallest memory footprint, slowest matching.
+pub const ENGINE_KIND_NONCONTIGUOUS_NFA: u32 = 3 << 4;
+const ENGINE_KIND_MASK: u32 = 0b11 << 4;
+
+// Sibling of ROUTE_PATTERN_COUNTS: the automaton kind chosen per route, kept as
+// the `ENGINE_KIND_*` code so the control plane can introspect the tradeoff.
+static ROUTE_KIND: Lazy<RwLock<HashMap<u32, u32>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Atomic scan/match counters kept per route for metrics export
+/// (Prometheus-style scraping).
+#[derive(Default)]
+struct RouteStats {
+    scans: AtomicU64,
+    matches: AtomicU64,
+}
+
+/// Everything the matching hot path needs for one route, published as a single
+/// `Arc` inside [`ROUTE_ENGINES`] so a query reads it lock-free. Flags are kept
+/// here because anchored search must be requested at search time, and the stats
+/// counters are atomic so reads and increments need no surrounding lock.
+struct RouteEngine {
+    ac: Arc<AhoCorasick>,
+    flags: u32,
+    stats: RouteStats,
+}
+
+/// State backing one open streaming scan.
+struct StreamState {
+    ac: Arc<AhoCorasick>,
+    // Route flags, kept so streaming honors anchored search like the other APIs.
+    flags: u32,
+    // Number of trailing bytes retained between feeds: `max_pattern_len - 1`,
+    // the smallest window that cannot drop a cross-boundary match.
+    carry_len: usize,
+    carry: Vec<u8>,
+    // True while the carry-over still begins at the very start of the stream
+    // (every byte fed so far fits in it); an anchored match can only start
+    // there, so only a feed made while this holds can produce one.
+    at_stream_start: bool,
+    // Latched once any feed has matched, so later feeds keep reporting a hit.
+    matched: bool,
+}
 
 // ---------- Helpers ----------
 
-fn decode_b64_json_patterns(blob: *const u8, len: usize) -> Result<Vec<String>, i32> {
+/// Decode a base64(JSON) rule blob into the pattern list fed to Aho–Corasick
+/// and the parallel metadata vector.
+fn decode_b64_json_rules(blob: *const u8, len: usize) -> Result<(Vec<String>, Vec<RuleMeta>), i32> {
     if blob.is_null() || len == 0 {
         return Err(-1);
     }
@@ -43,14 +161,117 @@ fn decode_b64_json_patterns(blob: *const u8, len: usize) -> Result<Vec<String>,
 
     let decoded = STANDARD.decode(b64_str).map_err(|_| -2)?;
 
-    // Expect plain JSON array of strings: ["pat1","pat2",...]
-    let patterns: Vec<String> = serde_json::from_slice(&decoded).map_err(|_| -3)?;
+    // Accept either ["pat",...] or [{"pattern":...},...] (or a mix).
+    let entries: Vec<RuleEntry> = serde_json::from_slice(&decoded).map_err(|_| -3)?;
 
     // Optional: enforce limits here to avoid abuse.
     // e.g.:
-    // if patterns.len() > 10_000 { return Err(-3); }
+    // if entries.len() > 10_000 { return Err(-3); }
+
+    let mut patterns = Vec::with_capacity(entries.len());
+    let mut metas = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match entry {
+            RuleEntry::Plain(pattern) => {
+                metas.push(RuleMeta {
+                    id: pattern.clone(),
+                    severity: 0,
+                    action: String::new(),
+                });
+                patterns.push(pattern);
+            }
+            RuleEntry::Rich(obj) => {
+                metas.push(RuleMeta {
+                    id: obj.id.unwrap_or_else(|| obj.pattern.clone()),
+                    severity: obj.severity,
+                    action: obj.action.unwrap_or_default(),
+                });
+                patterns.push(obj.pattern);
+            }
+        }
+    }
+
+    Ok((patterns, metas))
+}
+
+/// Build an `AhoCorasick` automaton honoring the per-route `flags`.
+fn build_automaton(patterns: &[String], flags: u32) -> Result<AhoCorasick, i32> {
+    let mut builder = AhoCorasickBuilder::new();
 
-    Ok(patterns)
+    if flags & ENGINE_FLAG_CASE_INSENSITIVE != 0 {
+        builder.ascii_case_insensitive(true);
+    }
+    if flags & ENGINE_FLAG_LEFTMOST_LONGEST != 0 {
+        builder.match_kind(MatchKind::LeftmostLongest);
+    }
+    if flags & ENGINE_FLAG_ANCHORED != 0 {
+        // Allow both so anchored and unanchored searches are valid.
+        builder.start_kind(StartKind::Both);
+    }
+    if flags & ENGINE_FLAG_PREFILTER != 0 {
+        builder.prefilter(true);
+    }
+    if let Some(kind) = automaton_kind(flags) {
+        builder.kind(Some(kind));
+    }
+
+    builder.build(patterns).map_err(|_| -4)
+}
+
+/// Decode the automaton-kind bits of `flags` into an [`AhoCorasickKind`].
+///
+/// `None` means leave the choice to the library (`ENGINE_KIND_AUTO`).
+fn automaton_kind(flags: u32) -> Option<AhoCorasickKind> {
+    match flags & ENGINE_KIND_MASK {
+        ENGINE_KIND_DFA => Some(AhoCorasickKind::DFA),
+        ENGINE_KIND_CONTIGUOUS_NFA => Some(AhoCorasickKind::ContiguousNFA),
+        ENGINE_KIND_NONCONTIGUOUS_NFA => Some(AhoCorasickKind::NoncontiguousNFA),
+        _ => None,
+    }
+}
+
+/// Build the search `Input` for a route, honoring the anchored flag so every
+/// matching entry point (`is_match`, `find_iter`) agrees on anchored routes.
+fn route_input<'h>(flags: u32, text: &'h str) -> Input<'h> {
+    let input = Input::new(text);
+    if flags & ENGINE_FLAG_ANCHORED != 0 {
+        input.anchored(Anchored::Yes)
+    } else {
+        input
+    }
+}
+
+/// Run `is_match` for a route, honoring the anchored flag at search time.
+fn route_is_match(ac: &AhoCorasick, flags: u32, text: &str) -> bool {
+    ac.is_match(route_input(flags, text))
+}
+
+/// Whether a route should iterate with overlapping search.
+///
+/// Overlapping iteration is only valid for `Standard` semantics, and it is what
+/// lets a superstring rule (`DROP TABLE`) be reported alongside a nested shorter
+/// rule (`DROP`). Routes built with [`ENGINE_FLAG_LEFTMOST_LONGEST`] cannot use
+/// overlapping iteration, but leftmost-longest already picks the longest match
+/// at each position, so plain `find_iter` reports the superstring there.
+fn route_overlapping(flags: u32) -> bool {
+    flags & ENGINE_FLAG_LEFTMOST_LONGEST == 0
+}
+
+/// Collect every matching rule for a route as `(pattern_index, start, end)`,
+/// honoring the anchored flag and using overlapping iteration where the
+/// match kind allows it so nested/overlapping rules are not dropped.
+fn route_matches(ac: &AhoCorasick, flags: u32, text: &str) -> Vec<(usize, usize, usize)> {
+    let mut out = Vec::new();
+    if route_overlapping(flags) {
+        for mat in ac.find_overlapping_iter(route_input(flags, text)) {
+            out.push((mat.pattern().as_usize(), mat.start(), mat.end()));
+        }
+    } else {
+        for mat in ac.find_iter(route_input(flags, text)) {
+            out.push((mat.pattern().as_usize(), mat.start(), mat.end()));
+        }
+    }
+    out
 }
 
 // ---------- FFI: Rule loading per route ----------
@@ -74,26 +295,67 @@ fn decode_b64_json_patterns(blob: *const u8, len: usize) -> Result<Vec<String>,
 ///  -4   = failed to build Aho–Corasick automaton
 #[no_mangle]
 pub extern "C" fn engine_load_route_rules(route_id: u32, blob: *const u8, len: usize) -> i32 {
-    let patterns = match decode_b64_json_patterns(blob, len) {
+    engine_load_route_rules_ex(route_id, blob, len, 0)
+}
+
+/// Load rules for a route with explicit match configuration.
+///
+/// Identical to [`engine_load_route_rules`] but takes a `flags` bitmask built
+/// from `ENGINE_FLAG_*`:
+///   - `ENGINE_FLAG_CASE_INSENSITIVE` — ASCII case-insensitive matching
+///   - `ENGINE_FLAG_LEFTMOST_LONGEST` — leftmost-longest instead of standard
+///   - `ENGINE_FLAG_ANCHORED` — only match anchored at the content start
+///   - `ENGINE_FLAG_PREFILTER` — enable rare-byte prefilters for throughput
+///   - `ENGINE_KIND_*` (bits 4-5) — pick the automaton kind; `DFA` is fastest
+///     but memory-hungry on large sets, the NFA kinds trade speed for memory
+///
+/// The chosen flags are stored per route because anchored search must be
+/// requested at search time as well as enabled at build time.
+#[no_mangle]
+pub extern "C" fn engine_load_route_rules_ex(
+    route_id: u32,
+    blob: *const u8,
+    len: usize,
+    flags: u32,
+) -> i32 {
+    let (patterns, metas) = match decode_b64_json_rules(blob, len) {
         Ok(p) => p,
         Err(code) => return code,
     };
 
-    let ac = match AhoCorasick::new(&patterns) {
+    let ac = match build_automaton(&patterns, flags) {
         Ok(ac) => ac,
-        Err(_) => return -4,
+        Err(code) => return code,
     };
 
-    {
-        let mut engines = ROUTE_ENGINES.write().unwrap();
-        engines.insert(route_id, Arc::new(ac));
-    }
+    // RCU: publish a new map with this route's engine swapped in. The Arc is
+    // cloned inside the closure because `rcu` may retry under a concurrent store.
+    let engine = Arc::new(RouteEngine {
+        ac: Arc::new(ac),
+        flags,
+        stats: RouteStats::default(),
+    });
+    ROUTE_ENGINES.rcu(|cur| {
+        let mut map = HashMap::clone(cur);
+        map.insert(route_id, Arc::clone(&engine));
+        map
+    });
 
     {
         let mut counts = ROUTE_PATTERN_COUNTS.write().unwrap();
         counts.insert(route_id, patterns.len());
     }
 
+    {
+        let mut meta = ROUTE_META.write().unwrap();
+        meta.insert(route_id, metas);
+    }
+
+    {
+        let mut kinds = ROUTE_KIND.write().unwrap();
+        kinds.insert(route_id, flags & ENGINE_KIND_MASK);
+    }
+
     0
 }
 
@@ -103,14 +365,23 @@ pub extern "C" fn engine_load_route_rules(route_id: u32, blob: *const u8, len: u
 ///   0 = success (even if route_id was not present)
 #[no_mangle]
 pub extern "C" fn engine_clear_route_rules(route_id: u32) -> i32 {
-    {
-        let mut engines = ROUTE_ENGINES.write().unwrap();
-        engines.remove(&route_id);
-    }
+    ROUTE_ENGINES.rcu(|cur| {
+        let mut map = HashMap::clone(cur);
+        map.remove(&route_id);
+        map
+    });
     {
         let mut counts = ROUTE_PATTERN_COUNTS.write().unwrap();
         counts.remove(&route_id);
     }
+    {
+        let mut meta = ROUTE_META.write().unwrap();
+        meta.remove(&route_id);
+    }
+    {
+        let mut kinds = ROUTE_KIND.write().unwrap();
+        kinds.remove(&route_id);
+    }
     0
 }
 
@@ -120,14 +391,19 @@ pub extern "C" fn engine_clear_route_rules(route_id: u32) -> i32 {
 ///   0 = success
 #[no_mangle]
 pub extern "C" fn engine_clear_all_rules() -> i32 {
-    {
-        let mut engines = ROUTE_ENGINES.write().unwrap();
-        engines.clear();
-    }
+    ROUTE_ENGINES.store(Arc::new(HashMap::new()));
     {
         let mut counts = ROUTE_PATTERN_COUNTS.write().unwrap();
         counts.clear();
     }
+    {
+        let mut meta = ROUTE_META.write().unwrap();
+        meta.clear();
+    }
+    {
+        let mut kinds = ROUTE_KIND.write().unwrap();
+        kinds.clear();
+    }
     0
 }
 
@@ -154,24 +430,344 @@ pub extern "C" fn engine_check_response_for_route(
         Err(_) => return 0,
     };
 
-    // Clone Arc under read lock, then drop lock before matching
-    let ac_opt: Option<Arc<AhoCorasick>> = {
-        let engines = ROUTE_ENGINES.read().unwrap();
-        engines.get(&route_id).cloned()
+    // Wait-free load of the route engine; the guard keeps it alive while we
+    // match and bump the atomic counters, with no lock anywhere on this path.
+    let engines = ROUTE_ENGINES.load();
+    let engine = match engines.get(&route_id) {
+        Some(e) => e,
+        None => return 0, // no rules for this route
     };
 
-    let ac = match ac_opt {
-        Some(ac) => ac,
+    let matched = route_is_match(&engine.ac, engine.flags, text);
+
+    engine.stats.scans.fetch_add(1, Ordering::Relaxed);
+    if matched {
+        engine.stats.matches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    if matched {
+        1
+    } else {
+        0
+    }
+}
+
+/// A single match reported by [`engine_scan_response_for_route`].
+///
+/// `pattern_index` is the zero-based position of the pattern in the list that
+/// was loaded for the route, so Lua can map it back to rule identity. `start`
+/// and `end` are byte offsets into the scanned content.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EngineMatch {
+    pub pattern_index: u32,
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Scan a response (C string) against rules for the given route_id and report
+/// *which* patterns matched and *where*.
+///
+/// Unlike [`engine_check_response_for_route`], this fills a caller-provided
+/// array of [`EngineMatch`] and returns the number of matches written (capped
+/// at `max_matches`). Standard-semantics routes report overlapping/nested
+/// matches too; `ENGINE_FLAG_LEFTMOST_LONGEST` routes report the leftmost
+/// longest match at each position.
+///
+/// Returns:
+///   n>=0 = number of matches written into `out_matches`
+///   -1   = null content/output pointer or route not configured
+#[no_mangle]
+pub extern "C" fn engine_scan_response_for_route(
+    route_id: u32,
+    content: *const c_char,
+    out_matches: *mut EngineMatch,
+    max_matches: usize,
+) -> i32 {
+    if content.is_null() || out_matches.is_null() {
+        return -1;
+    }
+
+    // SAFETY: null-terminated C string
+    let cstr = unsafe { CStr::from_ptr(content) };
+    let text = match cstr.to_str() {
+        Ok(s) => s,
+        Err(_) => return -1,
+    };
+
+    // Wait-free load of the route engine; the guard keeps it alive while we scan.
+    let engines = ROUTE_ENGINES.load();
+    let engine = match engines.get(&route_id) {
+        Some(e) => e,
+        None => return -1, // no rules for this route
+    };
+
+    // SAFETY: caller guarantees out_matches points to at least max_matches slots.
+    let out = unsafe { slice::from_raw_parts_mut(out_matches, max_matches) };
+
+    let mut written = 0usize;
+    for (pattern_index, start, end) in route_matches(&engine.ac, engine.flags, text) {
+        if written >= max_matches {
+            break;
+        }
+        out[written] = EngineMatch {
+            pattern_index: pattern_index as u32,
+            start: start as u32,
+            end: end as u32,
+        };
+        written += 1;
+    }
+
+    written as i32
+}
+
+/// Evaluate a response against a route's rules and return the highest severity
+/// among the rules that matched.
+///
+/// This lets the agent distinguish "log" vs "block" decisions from a single
+/// lookup: severity is taken from the rich rule metadata loaded for the route
+/// (plain string rules carry severity 0).
+///
+/// Returns:
+///   n>=0 = highest matched severity (0 = no match or route not configured)
+///   -1   = null/invalid content
+#[no_mangle]
+pub extern "C" fn engine_evaluate_route(route_id: u32, content: *const c_char) -> i32 {
+    if content.is_null() {
+        return -1;
+    }
+
+    // SAFETY: null-terminated C string
+    let cstr = unsafe { CStr::from_ptr(content) };
+    let text = match cstr.to_str() {
+        Ok(s) => s,
+        Err(_) => return -1,
+    };
+
+    let engines = ROUTE_ENGINES.load();
+    let engine = match engines.get(&route_id) {
+        Some(e) => e,
         None => return 0, // no rules for this route
     };
 
-    if ac.is_match(text) {
+    let meta = ROUTE_META.read().unwrap();
+    let metas = match meta.get(&route_id) {
+        Some(m) => m,
+        None => return 0,
+    };
+
+    let mut highest = 0u32;
+    for (pattern_index, _, _) in route_matches(&engine.ac, engine.flags, text) {
+        if let Some(rule) = metas.get(pattern_index) {
+            if rule.severity > highest {
+                highest = rule.severity;
+            }
+        }
+    }
+
+    highest as i32
+}
+
+// ---------- FFI: Streaming scan across chunk boundaries ----------
+
+/// Open a streaming scanner for `route_id`.
+///
+/// Returns an opaque handle (> 0) to pass to [`engine_stream_feed`] and
+/// [`engine_stream_close`], or 0 if the route is not configured.
+#[no_mangle]
+pub extern "C" fn engine_stream_open(route_id: u32) -> u64 {
+    let (ac, flags) = {
+        let engines = ROUTE_ENGINES.load();
+        match engines.get(&route_id) {
+            Some(e) => (Arc::clone(&e.ac), e.flags),
+            None => return 0,
+        }
+    };
+
+    // Retain longest_pattern - 1 bytes so a match spanning two feeds survives.
+    let carry_len = ac.max_pattern_len().saturating_sub(1);
+
+    let handle = STREAM_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let state = StreamState {
+        ac,
+        flags,
+        carry_len,
+        carry: Vec::with_capacity(carry_len),
+        at_stream_start: true,
+        matched: false,
+    };
+
+    STREAMS.write().unwrap().insert(handle, state);
+    handle
+}
+
+/// Feed the next body buffer into an open stream.
+///
+/// The carry-over tail from the previous feed is prepended before matching, and
+/// the trailing `max_pattern_len - 1` bytes are retained for the next call.
+///
+/// Anchored routes (`ENGINE_FLAG_ANCHORED`) are honored consistently with the
+/// other APIs: an anchored pattern can only match at the very start of the
+/// stream, so only feeds made while the carry-over still holds the stream's
+/// first bytes (fewer than `max_pattern_len` bytes fed before) can report a hit.
+///
+/// Returns:
+///   1 = a pattern has matched at some point in this stream (latched)
+///   0 = no match yet
+///  -1 = unknown handle or null buffer
+#[no_mangle]
+pub extern "C" fn engine_stream_feed(handle: u64, ptr: *const u8, len: usize) -> i32 {
+    if ptr.is_null() {
+        return -1;
+    }
+
+    let mut streams = STREAMS.write().unwrap();
+    let state = match streams.get_mut(&handle) {
+        Some(s) => s,
+        None => return -1,
+    };
+
+    // SAFETY: caller guarantees ptr+len is a valid buffer.
+    let chunk = unsafe { slice::from_raw_parts(ptr, len) };
+
+    // Prepend the carry-over so a pattern split across the boundary is seen.
+    let mut buf = Vec::with_capacity(state.carry.len() + chunk.len());
+    buf.extend_from_slice(&state.carry);
+    buf.extend_from_slice(chunk);
+
+    if !state.matched {
+        if state.flags & ENGINE_FLAG_ANCHORED != 0 {
+            // Anchored patterns can only match at the start of the stream, which
+            // `buf` begins with only while the carry-over still holds it.
+            if state.at_stream_start
+                && state.ac.is_match(Input::new(&buf).anchored(Anchored::Yes))
+            {
+                state.matched = true;
+            }
+        } else if state.ac.is_match(&buf[..]) {
+            state.matched = true;
+        }
+    }
+    // The new carry-over still begins at the stream start only if all of `buf` fits.
+    state.at_stream_start = state.at_stream_start && buf.len() <= state.carry_len;
+
+    // Retain the trailing carry_len bytes as the new carry-over.
+    let keep = state.carry_len.min(buf.len());
+    state.carry = buf[buf.len() - keep..].to_vec();
+
+    if state.matched {
         1
     } else {
         0
     }
 }
 
+/// Close a streaming scanner and free its state.
+///
+/// Returns 0 on success, -1 if the handle was unknown.
+#[no_mangle]
+pub extern "C" fn engine_stream_close(handle: u64) -> i32 {
+    if STREAMS.write().unwrap().remove(&handle).is_some() {
+        0
+    } else {
+        -1
+    }
+}
+
+// ---------- FFI: Introspection and metrics ----------
+
+/// Read the pattern count and match statistics for a route.
+///
+/// Any of the out-pointers may be null to skip that field. The counters are
+/// the totals accumulated since the route was loaded (or last cleared).
+///
+/// Returns:
+///   0 = success
+///  -1 = route not configured
+#[no_mangle]
+pub extern "C" fn engine_get_route_stats(
+    route_id: u32,
+    out_pattern_count: *mut u64,
+    out_scans: *mut u64,
+    out_matches: *mut u64,
+) -> i32 {
+    let engines = ROUTE_ENGINES.load();
+    let engine = match engines.get(&route_id) {
+        Some(e) => e,
+        None => return -1,
+    };
+    let route_stats = &engine.stats;
+
+    if !out_pattern_count.is_null() {
+        let count = ROUTE_PATTERN_COUNTS
+            .read()
+            .unwrap()
+            .get(&route_id)
+            .copied()
+            .unwrap_or(0) as u64;
+        // SAFETY: caller guarantees the pointer is valid when non-null.
+        unsafe { *out_pattern_count = count };
+    }
+    if !out_scans.is_null() {
+        // SAFETY: caller guarantees the pointer is valid when non-null.
+        unsafe { *out_scans = route_stats.scans.load(Ordering::Relaxed) };
+    }
+    if !out_matches.is_null() {
+        // SAFETY: caller guarantees the pointer is valid when non-null.
+        unsafe { *out_matches = route_stats.matches.load(Ordering::Relaxed) };
+    }
+
+    0
+}
+
+/// Enumerate the configured route_ids into a caller-provided array.
+///
+/// Writes up to `max` ids and returns the number written.
+///
+/// Returns:
+///   n>=0 = number of route_ids written into `out_ids`
+///   -1   = null output pointer
+#[no_mangle]
+pub extern "C" fn engine_list_routes(out_ids: *mut u32, max: usize) -> i32 {
+    if out_ids.is_null() {
+        return -1;
+    }
+
+    // SAFETY: caller guarantees out_ids points to at least max slots.
+    let out = unsafe { slice::from_raw_parts_mut(out_ids, max) };
+
+    let counts = ROUTE_PATTERN_COUNTS.read().unwrap();
+    let mut written = 0usize;
+    for &route_id in counts.keys() {
+        if written >= max {
+            break;
+        }
+        out[written] = route_id;
+        written += 1;
+    }
+
+    written as i32
+}
+
+/// Read the automaton-kind code chosen for a route.
+///
+/// The returned value is the `ENGINE_KIND_*` code (bits 4-5 of the load flags),
+/// letting the control plane introspect the memory/speed tradeoff it selected —
+/// `ENGINE_KIND_DFA` trades memory for matching speed, the NFA kinds the reverse.
+///
+/// Returns:
+///   n>=0 = the route's `ENGINE_KIND_*` code (`ENGINE_KIND_AUTO` == 0)
+///   -1   = route not configured
+#[no_mangle]
+pub extern "C" fn engine_get_route_kind(route_id: u32) -> i32 {
+    ROUTE_KIND
+        .read()
+        .unwrap()
+        .get(&route_id)
+        .map(|&kind| kind as i32)
+        .unwrap_or(-1)
+}
+
 // ---------- Backward-compatible global API (route_id = 0) ----------
 
 /// Legacy: load global rules without route.
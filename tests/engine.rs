@@ -0,0 +1,172 @@
+//! Integration tests exercising the route-scanning FFI end to end.
+//!
+//! The functions under test are `extern "C"` with raw-pointer arguments, so the
+//! tests drive them exactly as the Lua side does: base64(JSON) rule blobs in,
+//! C strings out. Each test uses its own `route_id` to stay independent of the
+//! process-global route tables.
+
+use std::ffi::CString;
+use std::ptr;
+
+use engine_rs::{
+    EngineMatch, ENGINE_FLAG_ANCHORED, engine_check_response_for_route, engine_clear_route_rules,
+    engine_evaluate_route, engine_load_route_rules, engine_load_route_rules_ex,
+    engine_scan_response_for_route, engine_stream_close, engine_stream_feed, engine_stream_open,
+};
+
+// Base64(JSON) rule blobs. The decoded JSON is shown alongside each constant.
+// ["DROP TABLE","rm -rf"]
+const PLAIN: &[u8] = b"WyJEUk9QIFRBQkxFIiwicm0gLXJmIl0=";
+// [{"id":"SQLI-1","pattern":"DROP TABLE","severity":5,"action":"block"},
+//  {"id":"LOG-1","pattern":"rm -rf","severity":2,"action":"log"}]
+const RICH: &[u8] = b"W3siaWQiOiJTUUxJLTEiLCJwYXR0ZXJuIjoiRFJPUCBUQUJMRSIsInNldmVyaXR5Ijo1LCJhY3Rpb24iOiJibG9jayJ9LHsiaWQiOiJMT0ctMSIsInBhdHRlcm4iOiJybSAtcmYiLCJzZXZlcml0eSI6MiwiYWN0aW9uIjoibG9nIn1d";
+// [{"id":"A","pattern":"bad","severity":3,"action":"block"}]
+const ANCHOR_RICH: &[u8] = b"W3siaWQiOiJBIiwicGF0dGVybiI6ImJhZCIsInNldmVyaXR5IjozLCJhY3Rpb24iOiJibG9jayJ9XQ==";
+
+fn load(route_id: u32, blob: &[u8]) {
+    assert_eq!(
+        engine_load_route_rules(route_id, blob.as_ptr(), blob.len()),
+        0
+    );
+}
+
+fn check(route_id: u32, content: &str) -> i32 {
+    let c = CString::new(content).unwrap();
+    engine_check_response_for_route(route_id, c.as_ptr())
+}
+
+fn evaluate(route_id: u32, content: &str) -> i32 {
+    let c = CString::new(content).unwrap();
+    engine_evaluate_route(route_id, c.as_ptr())
+}
+
+fn scan(route_id: u32, content: &str) -> Vec<(u32, u32, u32)> {
+    let c = CString::new(content).unwrap();
+    let mut out = vec![
+        EngineMatch {
+            pattern_index: 0,
+            start: 0,
+            end: 0
+        };
+        16
+    ];
+    let n = engine_scan_response_for_route(route_id, c.as_ptr(), out.as_mut_ptr(), out.len());
+    assert!(n >= 0);
+    out.into_iter()
+        .take(n as usize)
+        .map(|m| (m.pattern_index, m.start, m.end))
+        .collect()
+}
+
+#[test]
+fn plain_and_rich_blobs_are_both_accepted() {
+    // Backward-compat: the legacy string-array form still loads and matches.
+    load(1, PLAIN);
+    assert_eq!(check(1, "... DROP TABLE users ..."), 1);
+    assert_eq!(check(1, "nothing to see here"), 0);
+    // Plain rules carry no severity, so evaluate reports a match as severity 0.
+    assert_eq!(evaluate(1, "... DROP TABLE ..."), 0);
+    engine_clear_route_rules(1);
+
+    // Rich form threads severity through to the verdict API.
+    load(2, RICH);
+    assert_eq!(check(2, "rm -rf /"), 1);
+    assert_eq!(evaluate(2, "rm -rf /"), 2);
+    assert_eq!(evaluate(2, "DROP TABLE"), 5);
+    // Highest severity across all matched rules wins.
+    assert_eq!(evaluate(2, "rm -rf / && DROP TABLE"), 5);
+    engine_clear_route_rules(2);
+}
+
+#[test]
+fn scan_reports_pattern_index_and_offsets() {
+    load(3, RICH);
+    let matches = scan(3, "DROP TABLE");
+    assert_eq!(matches, vec![(0, 0, 10)]);
+    engine_clear_route_rules(3);
+}
+
+#[test]
+fn streaming_matches_across_a_chunk_boundary() {
+    load(4, PLAIN);
+    let handle = engine_stream_open(4);
+    assert!(handle > 0);
+
+    // `DROP TABLE` straddles the two feeds; the carry-over must bridge it.
+    let first = b"xxxDROP TA";
+    let second = b"BLE yyy";
+    assert_eq!(
+        engine_stream_feed(handle, first.as_ptr(), first.len()),
+        0,
+        "no match should be reported mid-pattern"
+    );
+    assert_eq!(
+        engine_stream_feed(handle, second.as_ptr(), second.len()),
+        1,
+        "the cross-boundary match must latch on the second feed"
+    );
+
+    assert_eq!(engine_stream_close(handle), 0);
+    engine_clear_route_rules(4);
+}
+
+#[test]
+fn streaming_honors_anchored_flag() {
+    // An anchored route matches only when the pattern sits at the stream start.
+    load_ex(6, ANCHOR_RICH, ENGINE_FLAG_ANCHORED);
+
+    let h1 = engine_stream_open(6);
+    let start = b"bad stuff follows";
+    assert_eq!(engine_stream_feed(h1, start.as_ptr(), start.len()), 1);
+    assert_eq!(engine_stream_close(h1), 0);
+
+    let h2 = engine_stream_open(6);
+    let mid = b"prefix then bad";
+    assert_eq!(engine_stream_feed(h2, mid.as_ptr(), mid.len()), 0);
+    assert_eq!(engine_stream_close(h2), 0);
+
+    engine_clear_route_rules(6);
+}
+
+#[test]
+fn anchored_verdict_apis_agree() {
+    // Regression: check/scan/evaluate must agree on an anchored route.
+    load_ex(5, ANCHOR_RICH, ENGINE_FLAG_ANCHORED);
+
+    // Anchored at the start: all three APIs report a hit.
+    assert_eq!(check(5, "badxx"), 1);
+    assert_eq!(scan(5, "badxx").len(), 1);
+    assert_eq!(evaluate(5, "badxx"), 3);
+
+    // Not at the start: all three APIs agree there is no match.
+    assert_eq!(check(5, "xxbad"), 0);
+    assert_eq!(scan(5, "xxbad").len(), 0);
+    assert_eq!(evaluate(5, "xxbad"), 0);
+
+    engine_clear_route_rules(5);
+}
+
+fn load_ex(route_id: u32, blob: &[u8], flags: u32) {
+    assert_eq!(
+        engine_load_route_rules_ex(route_id, blob.as_ptr(), blob.len(), flags),
+        0
+    );
+}
+
+#[test]
+fn unknown_route_yields_no_match_or_error() {
+    assert_eq!(check(999, "DROP TABLE"), 0);
+    assert_eq!(evaluate(999, "DROP TABLE"), 0);
+    let c = CString::new("DROP TABLE").unwrap();
+    let mut out = [EngineMatch {
+        pattern_index: 0,
+        start: 0,
+        end: 0,
+    }];
+    assert_eq!(
+        engine_scan_response_for_route(999, c.as_ptr(), out.as_mut_ptr(), out.len()),
+        -1
+    );
+    assert_eq!(engine_stream_open(999), 0);
+    assert_eq!(engine_stream_feed(0, ptr::null(), 0), -1);
+}